@@ -38,32 +38,42 @@
 //! ```
 
 use std::{
-    ffi::OsStr,
-    path::Path,
-    process::{Command, ExitStatus},
+    ffi::{OsStr, OsString},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Output, Stdio},
 };
 
 use cfg_if::cfg_if;
 
 cfg_if! {
     if #[cfg(target_family = "windows")] {
-        const CMD: &str = "cmd.exe";
-        const OPT: &str = "/C";
+        const NPM: &str = "npm.cmd";
+        const PNPM: &str = "pnpm.cmd";
+        const YARN: &str = "yarn.cmd";
+        const BUN: &str = "bun.exe";
     } else {
-        const CMD: &str = "bash";
-        const OPT: &str = "-c";
+        const NPM: &str = "npm";
+        const PNPM: &str = "pnpm";
+        const YARN: &str = "yarn";
+        const BUN: &str = "bun";
     }
 }
 
 const NODE_ENV: &str = "NODE_ENV";
+const PATH_VAR: &str = "PATH";
+
+const PACKAGE_JSON: &str = "package.json";
+const PACKAGE_LOCK: &str = "package-lock.json";
 
-const NPM: &str = "npm";
 const NPM_INIT: &str = "init";
 const NPM_INSTALL: &str = "install";
 const NPM_UNINSTALL: &str = "uninstall";
 const NPM_UPDATE: &str = "update";
 const NPM_RUN: &str = "run";
 
+const IGNORE_SCRIPTS: &str = "--ignore-scripts";
+
 /// This enum is used to determine the desired `NODE_ENV` variable value. Its value by [`Default`] is [`NodeEnv::Development`]
 ///
 /// Can be retrieved from Cargo env var `PROFILE` using [`NodeEnv::from_cargo()`](NodeEnv::from_cargo) or created manually.
@@ -73,6 +83,42 @@ pub enum NodeEnv {
     Custom(String),
 }
 
+/// This enum represents a JS package manager that can be used in place of `npm`.
+///
+/// Use [`NpmEnv::with_package_manager`] or [`NpmEnv::with_package_manager_fallback`] to select
+/// one (or an ordered fallback chain) for [`Npm::exec()`] to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackageManager {
+    #[default]
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    fn binary(&self) -> &'static str {
+        match self {
+            Self::Npm => NPM,
+            Self::Pnpm => PNPM,
+            Self::Yarn => YARN,
+            Self::Bun => BUN,
+        }
+    }
+
+    /// Translates a generic `npm_cmd` verb (as queued by [`Npm`]) into this manager's
+    /// equivalent subcommand, taking into account whether extra arguments were given.
+    fn verb(&self, npm_cmd: &str, has_args: bool) -> String {
+        match (self, npm_cmd) {
+            (Self::Npm, _) => npm_cmd.to_string(),
+            (_, NPM_INSTALL) if has_args => "add".to_string(),
+            (_, NPM_UNINSTALL) => "remove".to_string(),
+            (Self::Yarn, NPM_UPDATE) => "upgrade".to_string(),
+            (_, other) => other.to_string(),
+        }
+    }
+}
+
 /// This struct is used to create the enviroment in which npm will execute commands.
 /// [`NpmEnv`] uses [`Command`] so it takes all the env variables in your system.
 ///
@@ -86,7 +132,13 @@ pub enum NodeEnv {
 ///                  .with_env("FOO", "bar")
 ///                  .init_env();
 /// ```
-pub struct NpmEnv(Command);
+pub struct NpmEnv {
+    cmd: Command,
+    watch: Vec<PathBuf>,
+    managers: Vec<PackageManager>,
+    ignore_scripts: bool,
+    allow_scripts: Vec<String>,
+}
 
 /// This struct is used to execute npm commands.
 /// Can be created from [`NpmEnv`] of using [`Default`].
@@ -101,7 +153,20 @@ pub struct NpmEnv(Command);
 /// ```
 pub struct Npm {
     cmd: Command,
+    args: Vec<QueuedCommand>,
+    watch: Vec<PathBuf>,
+    managers: Vec<PackageManager>,
+    ignore_scripts: bool,
+    allow_scripts: Vec<String>,
+}
+
+/// A queued `npm_cmd` invocation: its full argument chain (positional args plus any flags, e.g.
+/// `--ignore-scripts`) and whether positional arguments were actually given, tracked separately
+/// since [`PackageManager::verb`] must translate based on the latter, not on flag presence.
+struct QueuedCommand {
+    npm_cmd: String,
     args: Vec<String>,
+    has_args: bool,
 }
 
 impl Default for NodeEnv {
@@ -128,21 +193,73 @@ impl NodeEnv {
 
 impl Default for NpmEnv {
     fn default() -> Self {
-        let mut cmd = Command::new(CMD);
-        cmd.arg(OPT);
-        cmd.current_dir(std::env::current_dir().unwrap());
+        let mut cmd = Command::new(NPM);
+
+        let current_dir = std::env::current_dir().unwrap();
+        cmd.current_dir(&current_dir);
 
-        Self(cmd)
+        Self {
+            cmd,
+            watch: vec![
+                current_dir.join(PACKAGE_JSON),
+                current_dir.join(PACKAGE_LOCK),
+            ],
+            managers: vec![PackageManager::default()],
+            ignore_scripts: false,
+            allow_scripts: Vec::new(),
+        }
     }
 }
 
 impl Clone for NpmEnv {
     fn clone(&self) -> Self {
-        let mut cmd = Command::new(self.0.get_program());
-        cmd.args(self.0.get_args());
-        cmd.current_dir(self.0.get_current_dir().unwrap());
+        Self {
+            cmd: clone_command(&self.cmd),
+            watch: self.watch.clone(),
+            managers: self.managers.clone(),
+            ignore_scripts: self.ignore_scripts,
+            allow_scripts: self.allow_scripts.clone(),
+        }
+    }
+}
+
+/// Builds a fresh [`Command`] for `template`'s program, inheriting its working directory and
+/// environment variables. Used by [`NpmEnv`]'s [`Clone`] implementation to copy the
+/// environment configured through it.
+fn clone_command(template: &Command) -> Command {
+    let mut cmd = Command::new(template.get_program());
+    apply_env(&mut cmd, template);
+    cmd
+}
+
+/// Returns the `PATH` that `cmd` is actually configured to spawn with: an explicit `PATH`
+/// override on `cmd` (set e.g. via [`NpmEnv::with_node_path`]), case-insensitively, falling
+/// back to the real process `PATH` if `cmd` doesn't override it.
+fn configured_path(cmd: &Command) -> Option<OsString> {
+    cmd.get_envs()
+        .find(|(key, _)| {
+            key.to_str()
+                .is_some_and(|k| k.eq_ignore_ascii_case(PATH_VAR))
+        })
+        .map(|(_, val)| val.map(OsString::from))
+        .unwrap_or_else(|| std::env::var_os(PATH_VAR))
+}
 
-        Self(cmd)
+/// Copies `template`'s working directory and environment variables onto `cmd`.
+fn apply_env(cmd: &mut Command, template: &Command) {
+    if let Some(dir) = template.get_current_dir() {
+        cmd.current_dir(dir);
+    }
+
+    for (key, val) in template.get_envs() {
+        match val {
+            Some(val) => {
+                cmd.env(key, val);
+            }
+            None => {
+                cmd.env_remove(key);
+            }
+        }
     }
 }
 
@@ -164,7 +281,7 @@ impl NpmEnv {
         K: AsRef<OsStr>,
         V: AsRef<OsStr>,
     {
-        self.0.env(key, val);
+        self.cmd.env(key, val);
         self
     }
 
@@ -175,22 +292,40 @@ impl NpmEnv {
         K: AsRef<OsStr>,
         V: AsRef<OsStr>,
     {
-        self.0.envs(vars);
+        self.cmd.envs(vars);
         self
     }
 
     /// Clears the entire environment map for [`Npm`].
     pub fn clear_envs(mut self) -> Self {
-        self.0.env_clear();
+        self.cmd.env_clear();
         self
     }
 
+    /// Prepends `dir` to the `PATH` used to spawn the package manager, so a pinned Node/npm
+    /// install is resolved ahead of whatever is already on `PATH`. Useful for hermetic/CI
+    /// builds where the desired Node binary doesn't live on the default path.
+    pub fn with_node_path<P>(self, dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let current = configured_path(&self.cmd);
+
+        let mut paths = vec![dir.as_ref().to_path_buf()];
+        if let Some(current) = &current {
+            paths.extend(std::env::split_paths(current));
+        }
+
+        let path = std::env::join_paths(paths).expect("node path contains an invalid character");
+        self.with_env(PATH_VAR, path)
+    }
+
     /// Removes an enviroment variable mapping.
     pub fn remove_env<K>(mut self, key: K) -> Self
     where
         K: AsRef<OsStr>,
     {
-        self.0.env_remove(key);
+        self.cmd.env_remove(key);
         self
     }
 
@@ -199,7 +334,88 @@ impl NpmEnv {
     where
         P: AsRef<Path>,
     {
-        self.0.current_dir(path);
+        self.cmd.current_dir(path);
+        self
+    }
+
+    /// Watches the given paths for changes, emitting Cargo's
+    /// [`rerun-if-changed`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed)
+    /// instruction for each of them during [`Npm::exec()`].
+    ///
+    /// Directories are walked recursively, emitting one instruction per file found inside,
+    /// while skipping any `node_modules` directory along the way. Paths that don't exist emit
+    /// no instruction. This replaces the default
+    /// watch list (`package.json` and `package-lock.json` in the current directory).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use npm_rs::*;
+    ///
+    /// let exit_status = NpmEnv::default()
+    ///        .with_change_detection(&["package.json", "src/assets"])
+    ///        .init_env()
+    ///        .install(None)
+    ///        .exec()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_change_detection<P>(mut self, paths: &[P]) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.watch = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
+        self
+    }
+
+    /// Selects the package manager used to execute queued commands, in place of `npm`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use npm_rs::*;
+    ///
+    /// let exit_status = NpmEnv::default()
+    ///        .with_package_manager(PackageManager::Pnpm)
+    ///        .init_env()
+    ///        .install(None)
+    ///        .exec()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_package_manager(mut self, manager: PackageManager) -> Self {
+        self.managers = vec![manager];
+        self
+    }
+
+    /// Registers an ordered list of candidate package managers. [`Npm::exec()`] uses the
+    /// first one found on `PATH`, falling back to the next candidate if a binary is missing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use npm_rs::*;
+    ///
+    /// let exit_status = NpmEnv::default()
+    ///        .with_package_manager_fallback(&[PackageManager::Bun, PackageManager::Npm])
+    ///        .init_env()
+    ///        .install(None)
+    ///        .exec()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_package_manager_fallback(mut self, managers: &[PackageManager]) -> Self {
+        self.managers = managers.to_vec();
+        self
+    }
+
+    /// Passes `--ignore-scripts` to `install`/`update`/`custom` commands, a safer default for
+    /// CI that stops dependencies from running `preinstall`/`install`/`postinstall` lifecycle
+    /// scripts. Use [`NpmEnv::allow_scripts`] to exempt specific packages.
+    pub fn ignore_scripts(mut self) -> Self {
+        self.ignore_scripts = true;
+        self
+    }
+
+    /// Exempts the given packages from [`NpmEnv::ignore_scripts`]: an `install`/`update` call
+    /// whose target packages are all in this list runs without `--ignore-scripts`, letting
+    /// their lifecycle scripts execute.
+    pub fn allow_scripts(mut self, packages: &[&str]) -> Self {
+        self.allow_scripts = packages.iter().map(|s| s.to_string()).collect();
         self
     }
 
@@ -209,8 +425,12 @@ impl NpmEnv {
     /// For now, use `features = ["nightly"]` to clone the enviroment configuration.
     pub fn init_env(self) -> Npm {
         Npm {
-            cmd: self.0,
+            cmd: self.cmd,
             args: Default::default(),
+            watch: self.watch,
+            managers: self.managers,
+            ignore_scripts: self.ignore_scripts,
+            allow_scripts: self.allow_scripts,
         }
     }
 }
@@ -222,21 +442,30 @@ impl Default for Npm {
 }
 
 impl Npm {
-    fn npm_append(&mut self, npm_cmd: &str, chain: &[&str]) {
-        self.args.push(
-            [NPM, npm_cmd]
+    fn npm_append(&mut self, npm_cmd: &str, chain: &[&str], has_args: bool) {
+        self.args.push(QueuedCommand {
+            npm_cmd: npm_cmd.to_string(),
+            args: chain.iter().map(|s| s.to_string()).collect(),
+            has_args,
+        });
+    }
+
+    /// Whether `--ignore-scripts` should be added for a command targeting `pkgs`: `true` when
+    /// [`NpmEnv::ignore_scripts`] was set and `pkgs` isn't entirely covered by
+    /// [`NpmEnv::allow_scripts`].
+    fn should_ignore_scripts(&self, pkgs: &[&str]) -> bool {
+        let all_allowed = !pkgs.is_empty()
+            && pkgs
                 .iter()
-                .chain(chain)
-                .copied()
-                .collect::<Vec<_>>()
-                .join(" "),
-        );
+                .all(|pkg| self.allow_scripts.iter().any(|a| a == pkg));
+
+        self.ignore_scripts && !all_allowed
     }
 
     /// Same behaviour as [npm-init -y](https://docs.npmjs.com/cli/v7/commands/npm-init#yes).
     /// Initializes a package, creating a `package.json` file with the default template.
     pub fn init(mut self) -> Self {
-        self.npm_append(NPM_INIT, &["-y"]);
+        self.npm_append(NPM_INIT, &["-y"], false);
         self
     }
 
@@ -244,14 +473,21 @@ impl Npm {
     /// - If `args =`[`None`]: Installs all the dependencies listed in `package.json` into the local `node_modules` folder.
     /// - If `args =`[`Some`]: Installs any package in `args` into the local `node_modules` folder.
     pub fn install(mut self, args: Option<&[&str]>) -> Self {
-        self.npm_append(NPM_INSTALL, args.unwrap_or_default());
+        let pkgs = args.unwrap_or_default();
+        let has_args = !pkgs.is_empty();
+        let mut chain = pkgs.to_vec();
+        if self.should_ignore_scripts(pkgs) {
+            chain.push(IGNORE_SCRIPTS);
+        }
+
+        self.npm_append(NPM_INSTALL, &chain, has_args);
         self
     }
 
     /// Same behaviour as [npm-uninstall](https://docs.npmjs.com/cli/v7/commands/npm-uninstall).
     /// Uninstalls the given packages in `pkg`.
     pub fn uninstall(mut self, pkg: &[&str]) -> Self {
-        self.npm_append(NPM_UNINSTALL, pkg);
+        self.npm_append(NPM_UNINSTALL, pkg, !pkg.is_empty());
         self
     }
 
@@ -259,14 +495,21 @@ impl Npm {
     /// - If `args =`[`None`]: Updates all the local dependencies (local `node_modules` folder).
     /// - If `args =`[`Some`]: Updates any package in `pkg`.
     pub fn update(mut self, pkg: Option<&[&str]>) -> Self {
-        self.npm_append(NPM_UPDATE, pkg.unwrap_or_default());
+        let pkgs = pkg.unwrap_or_default();
+        let has_args = !pkgs.is_empty();
+        let mut chain = pkgs.to_vec();
+        if self.should_ignore_scripts(pkgs) {
+            chain.push(IGNORE_SCRIPTS);
+        }
+
+        self.npm_append(NPM_UPDATE, &chain, has_args);
         self
     }
 
     /// Same behaviour as [npm-run-script](https://docs.npmjs.com/cli/v7/commands/npm-run-script).
     /// Runs an arbitrary `command` from `package.json`'s "scripts" object.
     pub fn run(mut self, command: &str) -> Self {
-        self.args.push([NPM, NPM_RUN, command].join(" "));
+        self.npm_append(NPM_RUN, &[command], true);
         self
     }
 
@@ -284,11 +527,23 @@ impl Npm {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn custom(mut self, command: &str, args: Option<&[&str]>) -> Self {
-        self.npm_append(command, args.unwrap_or_default());
+        let pkgs = args.unwrap_or_default();
+        let has_args = !pkgs.is_empty();
+        let mut chain = pkgs.to_vec();
+        if self.should_ignore_scripts(pkgs) {
+            chain.push(IGNORE_SCRIPTS);
+        }
+
+        self.npm_append(command, &chain, has_args);
         self
     }
 
-    /// Executes all the commands in the invokation order used, waiting for its completion status.
+    /// Executes all the queued commands in the invokation order used, spawning the configured
+    /// package manager directly (no shell involved) once per command and stopping at the
+    /// first one that doesn't complete successfully.
+    ///
+    /// The package manager used is the first of [`NpmEnv::with_package_manager_fallback`]'s
+    /// candidates found on `PATH` (just `npm` by default).
     ///
     /// # Example
     /// ```no_run
@@ -298,8 +553,153 @@ impl Npm {
     /// assert!(status.success()); // Will `panic` if not completed successfully.
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn exec(mut self) -> Result<ExitStatus, std::io::Error> {
-        self.cmd.arg(self.args.join(" && "));
-        self.cmd.status()
+    pub fn exec(self) -> Result<ExitStatus, std::io::Error> {
+        self.run_watch();
+        let manager = self.pick_manager();
+
+        let mut status = ExitStatus::default();
+        for queued in &self.args {
+            status = self.command_for(manager, queued).status()?;
+            if !status.success() {
+                break;
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Same as [`Npm::exec()`], but captures stdout/stderr instead of inheriting them, so
+    /// callers can inspect what the package manager printed (e.g. to parse `npm ls` or assert
+    /// on `npm audit` output). Returns the [`Output`] of the last command run: either the one
+    /// that failed, or the final queued command on success.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use npm_rs::*;
+    ///
+    /// let output = Npm::default().custom("ls", Some(&["--json"])).exec_with_output()?;
+    /// assert!(output.status.success());
+    /// let stdout = String::from_utf8(output.stdout)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn exec_with_output(self) -> Result<Output, std::io::Error> {
+        self.run_watch();
+        let manager = self.pick_manager();
+
+        let mut output = Output {
+            status: ExitStatus::default(),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+
+        for queued in &self.args {
+            output = self.command_for(manager, queued).output()?;
+            if !output.status.success() {
+                break;
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Same as [`Npm::exec()`], but forwards the child's stdout/stderr line-by-line as it is
+    /// produced, instead of letting the command inherit the parent's handles up front. Useful
+    /// to interleave npm's output with other build script logging.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use npm_rs::*;
+    ///
+    /// let status = Npm::default().install(None).exec_streaming()?;
+    /// assert!(status.success());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn exec_streaming(self) -> Result<ExitStatus, std::io::Error> {
+        self.run_watch();
+        let manager = self.pick_manager();
+
+        let mut status = ExitStatus::default();
+        for queued in &self.args {
+            let mut cmd = self.command_for(manager, queued);
+            cmd.stdout(Stdio::piped());
+            cmd.stderr(Stdio::piped());
+
+            let mut child = cmd.spawn()?;
+            let stdout = child.stdout.take().expect("child stdout was piped");
+            let stderr = child.stderr.take().expect("child stderr was piped");
+
+            let stderr_thread = std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    eprintln!("{line}");
+                }
+            });
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                println!("{line}");
+            }
+            let _ = stderr_thread.join();
+
+            status = child.wait()?;
+            if !status.success() {
+                break;
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Emits the configured `rerun-if-changed` directives.
+    fn run_watch(&self) {
+        for path in &self.watch {
+            emit_rerun_if_changed(path);
+        }
+    }
+
+    /// Picks the first configured package manager found on the `PATH` this [`Npm`] is
+    /// configured to spawn with (see [`configured_path`]), falling back to the first configured
+    /// candidate (or [`PackageManager::Npm`]) if none is found.
+    fn pick_manager(&self) -> PackageManager {
+        let path = configured_path(&self.cmd);
+        self.managers
+            .iter()
+            .copied()
+            .find(|manager| find_on_path(&path, manager.binary()))
+            .or_else(|| self.managers.first().copied())
+            .unwrap_or_default()
+    }
+
+    /// Builds the [`Command`] for a single `queued` step, translated for `manager` and
+    /// configured with this [`Npm`]'s environment.
+    fn command_for(&self, manager: PackageManager, queued: &QueuedCommand) -> Command {
+        let mut cmd = Command::new(manager.binary());
+        apply_env(&mut cmd, &self.cmd);
+        cmd.arg(manager.verb(&queued.npm_cmd, queued.has_args))
+            .args(&queued.args);
+        cmd
+    }
+}
+
+/// Returns whether `bin` can be found in any directory listed in `path` (see [`configured_path`]).
+fn find_on_path(path: &Option<OsString>, bin: &str) -> bool {
+    path.as_ref()
+        .map(|paths| std::env::split_paths(paths).any(|dir| dir.join(bin).is_file()))
+        .unwrap_or(false)
+}
+
+/// Emits a Cargo `rerun-if-changed` directive for `path`. If `path` is a directory, it is
+/// walked recursively, emitting one directive per file found, skipping `node_modules`. If
+/// `path` doesn't exist, nothing is emitted.
+fn emit_rerun_if_changed(path: &Path) {
+    if path.is_dir() {
+        if path.file_name() == Some(OsStr::new("node_modules")) {
+            return;
+        }
+
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                emit_rerun_if_changed(&entry.path());
+            }
+        }
+    } else if path.is_file() {
+        println!("cargo:rerun-if-changed={}", path.display());
     }
 }